@@ -1,10 +1,14 @@
 extern crate regex;
 extern crate thiserror;
 
+use std::borrow::Cow;
 use std::io::Read;
+use std::ops::Range;
 
-#[derive(PartialEq, Debug)]
-enum TokenKind<'a> {
+mod parser;
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum TokenKind<'a> {
     Plus,
     Minus,
     Divide,
@@ -12,7 +16,7 @@ enum TokenKind<'a> {
     Modulo,
     IntegerLiteral(i64),
     FloatLiteral(f64),
-    StringLiteral(&'a str),
+    StringLiteral(Cow<'a, str>),
     Identifier(&'a str),
     Let,
     Var,
@@ -26,17 +30,27 @@ enum TokenKind<'a> {
     SemiColon,
     Colon,
     Equals,
+    EqualsEquals,
+    NotEquals,
+    Less,
+    LessEquals,
+    Greater,
+    GreaterEquals,
     Or,
     And,
     Xor,
+    LogicalAnd,
+    LogicalOr,
+    Arrow,
 }
 
 #[allow(dead_code)]
-#[derive(Debug)]
-struct Token<'a> {
-    kind: TokenKind<'a>,
-    line: usize,
-    col: usize,
+#[derive(Clone, Debug)]
+pub(crate) struct Token<'a> {
+    pub(crate) kind: TokenKind<'a>,
+    pub(crate) span: Range<usize>,
+    pub(crate) line: usize,
+    pub(crate) col: usize,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -47,6 +61,8 @@ enum LexerErrorKind {
     InvalidToken,
     #[error("Unexpected character '{0}'")]
     UnexpectedCharacter(char),
+    #[error("Invalid escape sequence '\\{0}'")]
+    InvalidEscape(char),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -59,47 +75,60 @@ struct LexerError<'a> {
     snippet: &'a str,
 }
 
-fn make_token<'a>(lexeme: &'a str, line: usize, col: usize) -> Result<Token<'a>, LexerErrorKind> {
+fn make_token<'a>(
+    lexeme: &'a str,
+    span: Range<usize>,
+    line: usize,
+    col: usize,
+) -> Result<Token<'a>, LexerErrorKind> {
     let col = col - (lexeme.len() - 1);
     match lexeme {
         "let" => Ok(Token {
             line,
             col,
+            span,
             kind: TokenKind::Let,
         }),
         "def" => Ok(Token {
             line,
             col,
+            span,
             kind: TokenKind::Def,
         }),
         "struct" => Ok(Token {
             line,
             col,
+            span,
             kind: TokenKind::Struct,
         }),
         "func" => Ok(Token {
             line,
             col,
+            span,
             kind: TokenKind::Func,
         }),
         "var" => Ok(Token {
             line,
             col,
+            span,
             kind: TokenKind::Var,
         }),
         "repeat" => Ok(Token {
             line,
             col,
+            span,
             kind: TokenKind::Repeat,
         }),
         "until" => Ok(Token {
             line,
             col,
+            span,
             kind: TokenKind::Until,
         }),
         "end" => Ok(Token {
             line,
             col,
+            span,
             kind: TokenKind::End,
         }),
         _ => {
@@ -108,6 +137,7 @@ fn make_token<'a>(lexeme: &'a str, line: usize, col: usize) -> Result<Token<'a>,
                     Ok(Token {
                         line,
                         col,
+                        span,
                         kind: TokenKind::IntegerLiteral(parsed_int),
                     })
                 } else {
@@ -118,6 +148,7 @@ fn make_token<'a>(lexeme: &'a str, line: usize, col: usize) -> Result<Token<'a>,
                     Ok(Token {
                         line,
                         col,
+                        span,
                         kind: TokenKind::IntegerLiteral(parsed_int),
                     })
                 } else {
@@ -127,12 +158,14 @@ fn make_token<'a>(lexeme: &'a str, line: usize, col: usize) -> Result<Token<'a>,
                 Ok(Token {
                     line,
                     col,
+                    span,
                     kind: TokenKind::IntegerLiteral(parsed),
                 })
             } else if let Ok(parsed_float) = lexeme.parse::<f64>() {
                 Ok(Token {
                     line,
                     col,
+                    span,
                     kind: TokenKind::FloatLiteral(parsed_float),
                 })
             } else {
@@ -141,6 +174,7 @@ fn make_token<'a>(lexeme: &'a str, line: usize, col: usize) -> Result<Token<'a>,
                     Ok(Token {
                         line,
                         col,
+                        span,
                         kind: TokenKind::Identifier(lexeme),
                     })
                 } else {
@@ -151,148 +185,786 @@ fn make_token<'a>(lexeme: &'a str, line: usize, col: usize) -> Result<Token<'a>,
     }
 }
 
-fn tokenize<'a>(code: &'a str, filename: &'a str) -> Result<Vec<Token<'a>>, LexerError<'a>> {
-    let mut tokens = vec![];
-    let mut iter = code.as_bytes().iter().enumerate().peekable();
-    let (mut line, mut col) = (1, 1);
-    let mut start = 0;
-    let mut i = 0;
-    while let Some((idx, scanned)) = iter.next() {
-        i = idx;
-        let scanned_token = match scanned {
-            b' ' | b'\t' => {
-                col += 1;
-                start = idx + 1;
-                continue;
-            }
+/// Decodes the escape sequence whose byte is `esc`, found at offset `esc_idx`
+/// (right after the `\`). Returns the decoded character and the absolute
+/// byte offset of the last byte the escape sequence consumed (just `esc_idx`
+/// itself for single-character escapes, further along for `\u{XXXX}`). On
+/// failure, the second element of the error tuple is the absolute byte
+/// offset of the last byte consumed before the failure, tracked the same way
+/// as the success path, so the caller's snippet doesn't silently drop bytes
+/// the escape sequence actually read.
+fn decode_escape<'a, I>(
+    esc: u8,
+    esc_idx: usize,
+    iter: &mut std::iter::Peekable<I>,
+) -> Result<(char, usize), (LexerErrorKind, usize)>
+where
+    I: Iterator<Item = (usize, &'a u8)>,
+{
+    match esc {
+        b'n' => Ok(('\n', esc_idx)),
+        b't' => Ok(('\t', esc_idx)),
+        b'r' => Ok(('\r', esc_idx)),
+        b'\\' => Ok(('\\', esc_idx)),
+        b'"' => Ok(('"', esc_idx)),
+        b'\'' => Ok(('\'', esc_idx)),
+        b'0' => Ok(('\0', esc_idx)),
+        b'u' => {
+            let brace_idx = match iter.next() {
+                Some((idx, b'{')) => idx,
+                Some((idx, _)) => return Err((LexerErrorKind::InvalidEscape('u'), idx)),
+                None => return Err((LexerErrorKind::InvalidEscape('u'), esc_idx)),
+            };
+            let mut hex = String::new();
+            let mut last_seen = brace_idx;
+            let last = loop {
+                match iter.next() {
+                    Some((idx, b'}')) => break idx,
+                    Some((idx, digit)) => {
+                        hex.push(*digit as char);
+                        last_seen = idx;
+                    }
+                    None => return Err((LexerErrorKind::UnexpectedEof("a '}'".into()), last_seen)),
+                }
+            };
+            let decoded = u32::from_str_radix(&hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or((LexerErrorKind::InvalidEscape('u'), last))?;
+            Ok((decoded, last))
+        }
+        other => Err((LexerErrorKind::InvalidEscape(other as char), esc_idx)),
+    }
+}
+
+/// Consumes a `#`/`//` line comment up to (but not including) the next
+/// newline or EOF.
+fn skip_line_comment<'a, I>(iter: &mut std::iter::Peekable<I>, col: &mut usize, i: &mut usize)
+where
+    I: Iterator<Item = (usize, &'a u8)>,
+{
+    while let Some((_, next)) = iter.peek() {
+        if **next == b'\n' {
+            break;
+        }
+        let (idx, _) = iter.next().unwrap();
+        *i = idx;
+        *col += 1;
+    }
+}
+
+/// Consumes a (possibly nested) `/* ... */` block comment, assuming the
+/// opening `/*` has already been consumed. `/* /* */ */` is balanced by
+/// tracking nesting depth; an embedded `\n` still bumps `line`/`col`.
+fn skip_block_comment<'a, I>(
+    iter: &mut std::iter::Peekable<I>,
+    line: &mut usize,
+    col: &mut usize,
+    i: &mut usize,
+) -> Result<(), LexerErrorKind>
+where
+    I: Iterator<Item = (usize, &'a u8)>,
+{
+    let mut depth = 1;
+    while let Some((idx, byte)) = iter.next() {
+        *i = idx;
+        match *byte {
             b'\n' => {
-                col = 1;
-                line += 1;
-                start = idx + 1;
-                continue;
+                *line += 1;
+                *col = 1;
+            }
+            b'*' if matches!(iter.peek(), Some((_, b'/'))) => {
+                iter.next();
+                *i += 1;
+                *col += 2;
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
             }
-            b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'0'..=b'9' | b'.' => match iter.peek() {
-                Some((next_idx, lookahead)) => match lookahead {
-                    b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'.' => {
-                        col += 1;
+            b'/' if matches!(iter.peek(), Some((_, b'*'))) => {
+                iter.next();
+                *i += 1;
+                *col += 2;
+                depth += 1;
+            }
+            _ => *col += 1,
+        }
+    }
+    Err(LexerErrorKind::UnexpectedEof("*/".into()))
+}
+
+/// Maximal-munch lookahead: if the next byte is `follow`, consumes it and
+/// bumps `col`/`i` to account for it, returning `true`. Used to turn a
+/// single-char operator into a two-char one (`=` + `=` -> `==`, etc.).
+fn munch<'a, I>(iter: &mut std::iter::Peekable<I>, col: &mut usize, i: &mut usize, follow: u8) -> bool
+where
+    I: Iterator<Item = (usize, &'a u8)>,
+{
+    match iter.peek() {
+        Some((_, next)) if **next == follow => {
+            iter.next();
+            *col += 1;
+            *i += 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Lexes `code` in full, bailing out on the first error. `main` uses
+/// [`tokenize_recovering`] instead so a user sees every problem in one run;
+/// this stricter entry point stays around for callers (and tests) that just
+/// want a `Vec<Token>` or the first error.
+#[allow(dead_code)]
+fn tokenize<'a>(code: &'a str, filename: &'a str) -> Result<Vec<Token<'a>>, LexerError<'a>> {
+    Lexer::new(code, filename).collect()
+}
+
+/// Lazily scans `code` one token at a time, holding exactly the state
+/// `tokenize` used to keep in local variables, so a consumer can pull tokens
+/// on demand instead of buffering the whole file up front. [`Iterator::next`]
+/// stops producing tokens after the first error; [`Lexer::scan_one`] is the
+/// same scan step without that fuse, for callers like [`tokenize_recovering`]
+/// that want to keep going past an error.
+pub(crate) struct Lexer<'a> {
+    code: &'a str,
+    filename: &'a str,
+    iter: std::iter::Peekable<std::iter::Enumerate<std::slice::Iter<'a, u8>>>,
+    line: usize,
+    col: usize,
+    start: usize,
+    i: usize,
+    fused: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub(crate) fn new(code: &'a str, filename: &'a str) -> Self {
+        Lexer {
+            code,
+            filename,
+            iter: code.as_bytes().iter().enumerate().peekable(),
+            line: 1,
+            col: 1,
+            start: 0,
+            i: 0,
+            fused: false,
+        }
+    }
+
+    /// Scans and returns the next token or error, with no fuse-on-error
+    /// behavior. `tokenize_recovering` drives this directly so it can resync
+    /// and keep scanning after an error instead of stopping.
+    fn scan_one(&mut self) -> Option<Result<Token<'a>, LexerError<'a>>> {
+        while let Some((idx, scanned)) = self.iter.next() {
+            self.i = idx;
+            let scanned_token = match scanned {
+                b' ' | b'\t' => {
+                    self.col += 1;
+                    self.start = idx + 1;
+                    continue;
+                }
+                b'\n' => {
+                    self.col = 1;
+                    self.line += 1;
+                    self.start = idx + 1;
+                    continue;
+                }
+                b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'0'..=b'9' | b'.' => match self.iter.peek() {
+                    Some((next_idx, lookahead)) => match lookahead {
+                        b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'.' => {
+                            self.col += 1;
+                            continue;
+                        }
+                        _ => make_token(
+                            &self.code[self.start..*next_idx],
+                            self.start..*next_idx,
+                            self.line,
+                            self.col,
+                        ),
+                    },
+                    None => make_token(
+                        &self.code[self.start..idx + 1],
+                        self.start..idx + 1,
+                        self.line,
+                        self.col,
+                    ),
+                },
+                b',' => Ok(Token {
+                    kind: TokenKind::Comma,
+                    span: idx..idx + 1,
+                    line: self.line,
+                    col: self.col,
+                }),
+                b':' => Ok(Token {
+                    kind: TokenKind::Colon,
+                    span: idx..idx + 1,
+                    line: self.line,
+                    col: self.col,
+                }),
+                b'=' => {
+                    let op_col = self.col;
+                    if munch(&mut self.iter, &mut self.col, &mut self.i, b'=') {
+                        Ok(Token {
+                            kind: TokenKind::EqualsEquals,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    } else {
+                        Ok(Token {
+                            kind: TokenKind::Equals,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    }
+                }
+                b'!' => {
+                    let op_col = self.col;
+                    if munch(&mut self.iter, &mut self.col, &mut self.i, b'=') {
+                        Ok(Token {
+                            kind: TokenKind::NotEquals,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    } else {
+                        Err(LexerErrorKind::UnexpectedCharacter('!'))
+                    }
+                }
+                b'<' => {
+                    let op_col = self.col;
+                    if munch(&mut self.iter, &mut self.col, &mut self.i, b'=') {
+                        Ok(Token {
+                            kind: TokenKind::LessEquals,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    } else {
+                        Ok(Token {
+                            kind: TokenKind::Less,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    }
+                }
+                b'>' => {
+                    let op_col = self.col;
+                    if munch(&mut self.iter, &mut self.col, &mut self.i, b'=') {
+                        Ok(Token {
+                            kind: TokenKind::GreaterEquals,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    } else {
+                        Ok(Token {
+                            kind: TokenKind::Greater,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    }
+                }
+                b';' => Ok(Token {
+                    kind: TokenKind::SemiColon,
+                    span: idx..idx + 1,
+                    line: self.line,
+                    col: self.col,
+                }),
+                b'|' => {
+                    let op_col = self.col;
+                    if munch(&mut self.iter, &mut self.col, &mut self.i, b'|') {
+                        Ok(Token {
+                            kind: TokenKind::LogicalOr,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    } else {
+                        Ok(Token {
+                            kind: TokenKind::Or,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    }
+                }
+                b'&' => {
+                    let op_col = self.col;
+                    if munch(&mut self.iter, &mut self.col, &mut self.i, b'&') {
+                        Ok(Token {
+                            kind: TokenKind::LogicalAnd,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    } else {
+                        Ok(Token {
+                            kind: TokenKind::And,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    }
+                }
+                b'^' => Ok(Token {
+                    kind: TokenKind::Xor,
+                    span: idx..idx + 1,
+                    line: self.line,
+                    col: self.col,
+                }),
+                b'#' => {
+                    skip_line_comment(&mut self.iter, &mut self.col, &mut self.i);
+                    self.start = self.i + 1;
+                    continue;
+                }
+                b'/' => match self.iter.peek() {
+                    Some((_, b'/')) => {
+                        skip_line_comment(&mut self.iter, &mut self.col, &mut self.i);
+                        self.start = self.i + 1;
                         continue;
                     }
-                    _ => make_token(&code[start..*next_idx], line, col),
+                    Some((_, b'*')) => {
+                        self.iter.next();
+                        self.col += 1;
+                        self.i += 1;
+                        match skip_block_comment(&mut self.iter, &mut self.line, &mut self.col, &mut self.i) {
+                            Ok(()) => {
+                                self.start = self.i + 1;
+                                continue;
+                            }
+                            Err(error_kind) => Err(error_kind),
+                        }
+                    }
+                    _ => Ok(Token {
+                        kind: TokenKind::Divide,
+                        span: idx..idx + 1,
+                        line: self.line,
+                        col: self.col,
+                    }),
                 },
-                None => make_token(&code[start..idx], line, col),
-            },
-            b',' => Ok(Token {
-                kind: TokenKind::Comma,
-                line,
-                col,
-            }),
-            b':' => Ok(Token {
-                kind: TokenKind::Colon,
-                line,
-                col,
-            }),
-            b'=' => Ok(Token {
-                kind: TokenKind::Equals,
-                line,
-                col,
-            }),
-            b';' => Ok(Token {
-                kind: TokenKind::SemiColon,
-                line,
-                col,
-            }),
-            b'|' => Ok(Token {
-                kind: TokenKind::Or,
-                line,
-                col,
-            }),
-            b'&' => Ok(Token {
-                kind: TokenKind::And,
-                line,
-                col,
-            }),
-            b'^' => Ok(Token {
-                kind: TokenKind::Xor,
-                line,
-                col,
-            }),
-            b'/' => Ok(Token {
-                kind: TokenKind::Divide,
-                line,
-                col,
-            }),
-            b'*' => Ok(Token {
-                kind: TokenKind::Multiply,
-                line,
-                col,
-            }),
-            b'+' => Ok(Token {
-                kind: TokenKind::Plus,
-                line,
-                col,
-            }),
-            b'-' => Ok(Token {
-                kind: TokenKind::Minus,
-                line,
-                col,
-            }),
-            b'%' => Ok(Token {
-                kind: TokenKind::Modulo,
-                line,
-                col,
-            }),
-            b'"' | b'\'' => loop {
-                // TODO: escape sequence
-                if let Some((x, chr)) = iter.next() {
-                    col += 1;
-                    i += 1;
-                    if chr == scanned {
-                        let lexeme = &code[start + 1..x];
-                        let len = lexeme.len() + 1;
-                        break Ok(Token {
-                            kind: TokenKind::StringLiteral(lexeme),
-                            line,
-                            col: col - len,
-                        });
+                b'*' => Ok(Token {
+                    kind: TokenKind::Multiply,
+                    span: idx..idx + 1,
+                    line: self.line,
+                    col: self.col,
+                }),
+                b'+' => Ok(Token {
+                    kind: TokenKind::Plus,
+                    span: idx..idx + 1,
+                    line: self.line,
+                    col: self.col,
+                }),
+                b'-' => {
+                    let op_col = self.col;
+                    if munch(&mut self.iter, &mut self.col, &mut self.i, b'>') {
+                        Ok(Token {
+                            kind: TokenKind::Arrow,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
+                    } else {
+                        Ok(Token {
+                            kind: TokenKind::Minus,
+                            span: idx..self.i + 1,
+                            line: self.line,
+                            col: op_col,
+                        })
                     }
-                } else {
-                    break Err(LexerErrorKind::UnexpectedEof(format!(
-                        "a '{}'",
-                        *scanned as char
-                    )));
                 }
-            },
-            _ => Err(LexerErrorKind::UnexpectedCharacter(*scanned as char)),
-        };
-        match scanned_token {
-            Ok(token) => tokens.push(token),
-            Err(error_kind) => {
-                return Err(LexerError {
+                b'%' => Ok(Token {
+                    kind: TokenKind::Modulo,
+                    span: idx..idx + 1,
+                    line: self.line,
+                    col: self.col,
+                }),
+                b'"' | b'\'' => {
+                    let mut owned: Option<String> = None;
+                    let mut seg_start = self.start + 1;
+                    let mut raw_len = 0usize;
+                    loop {
+                        if let Some((x, chr)) = self.iter.next() {
+                            self.col += 1;
+                            self.i += 1;
+                            raw_len += 1;
+                            if chr == scanned {
+                                let tail = &self.code[seg_start..x];
+                                let lexeme = match owned.take() {
+                                    Some(mut s) => {
+                                        s.push_str(tail);
+                                        Cow::Owned(s)
+                                    }
+                                    None => Cow::Borrowed(tail),
+                                };
+                                break Ok(Token {
+                                    kind: TokenKind::StringLiteral(lexeme),
+                                    span: idx..x + 1,
+                                    line: self.line,
+                                    col: self.col - raw_len,
+                                });
+                            } else if *chr == b'\\' {
+                                match self.iter.next() {
+                                    Some((y, esc)) => {
+                                        self.col += 1;
+                                        self.i += 1;
+                                        raw_len += 1;
+                                        match decode_escape(*esc, y, &mut self.iter) {
+                                            Ok((decoded, last_idx)) => {
+                                                let extra = last_idx - y;
+                                                self.col += extra;
+                                                self.i += extra;
+                                                raw_len += extra;
+                                                let tail = &self.code[seg_start..x];
+                                                let buf = owned.get_or_insert_with(String::new);
+                                                buf.push_str(tail);
+                                                buf.push(decoded);
+                                                seg_start = last_idx + 1;
+                                            }
+                                            Err((error_kind, last_idx)) => {
+                                                let extra = last_idx - y;
+                                                self.col += extra;
+                                                self.i += extra;
+                                                break Err(error_kind);
+                                            }
+                                        }
+                                    }
+                                    None => {
+                                        break Err(LexerErrorKind::UnexpectedEof(
+                                            "an escape sequence".into(),
+                                        ))
+                                    }
+                                }
+                            }
+                        } else {
+                            break Err(LexerErrorKind::UnexpectedEof(format!(
+                                "a '{}'",
+                                *scanned as char
+                            )));
+                        }
+                    }
+                }
+                _ => Err(LexerErrorKind::UnexpectedCharacter(*scanned as char)),
+            };
+            return Some(match scanned_token {
+                Ok(token) => {
+                    self.start = idx;
+                    self.col += 1;
+                    Ok(token)
+                }
+                Err(error_kind) => Err(LexerError {
                     error_kind,
-                    line,
-                    col,
-                    filename,
-                    snippet: &code[i - col + 1..=i],
-                })
+                    line: self.line,
+                    col: self.col,
+                    filename: self.filename,
+                    snippet: &self.code[self.start..=self.i],
+                }),
+            });
+        }
+        None
+    }
+
+    /// Skips ahead to the next whitespace or delimiter byte, so a single bad
+    /// byte doesn't produce one error per remaining byte in `code`.
+    fn resync(&mut self) {
+        while let Some((next_idx, next)) = self.iter.peek() {
+            if matches!(next, b' ' | b'\t' | b'\n' | b',' | b';' | b':') {
+                break;
             }
-        };
-        start = idx;
-        col += 1;
+            self.col += 1;
+            self.start = *next_idx + 1;
+            self.iter.next();
+        }
     }
-    Ok(tokens)
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>, LexerError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.fused {
+            return None;
+        }
+        let item = self.scan_one();
+        if matches!(item, Some(Err(_))) {
+            self.fused = true;
+        }
+        item
+    }
+}
+
+/// The result of lexing in recovery mode: whatever tokens could be scanned,
+/// plus every error encountered along the way.
+#[derive(Debug)]
+pub(crate) struct LexOutcome<'a> {
+    pub(crate) tokens: Vec<Token<'a>>,
+    pub(crate) errors: Vec<LexerError<'a>>,
+}
+
+/// Like [`tokenize`], but never bails on the first error. `UnexpectedCharacter`
+/// skips the offending byte and resyncs at the next whitespace or delimiter so
+/// a run of garbage doesn't produce one error per byte; an `UnexpectedEof`
+/// inside a string is unrecoverable, so it is recorded and scanning stops.
+fn tokenize_recovering<'a>(code: &'a str, filename: &'a str) -> LexOutcome<'a> {
+    let mut tokens = vec![];
+    let mut errors = vec![];
+    let mut lexer = Lexer::new(code, filename);
+    loop {
+        match lexer.scan_one() {
+            Some(Ok(token)) => tokens.push(token),
+            Some(Err(error)) => {
+                let is_eof_error = matches!(error.error_kind, LexerErrorKind::UnexpectedEof(_));
+                errors.push(error);
+                if is_eof_error {
+                    break;
+                }
+                lexer.resync();
+            }
+            None => break,
+        }
+    }
+    LexOutcome { tokens, errors }
 }
 
 fn main() -> std::io::Result<()> {
     let mut file = std::fs::File::open("test.jasm")?;
     let mut buf = String::new();
     file.read_to_string(&mut buf)?;
-    match tokenize(&buf, "test.jasm") {
-        Ok(tokens) => {
-            println!("{:?}", tokens);
+    let outcome = tokenize_recovering(&buf, "test.jasm");
+    println!("{:?}", outcome.tokens);
+    for error in &outcome.errors {
+        println!("{}", error);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lex_one(code: &str) -> Token<'_> {
+        let mut tokens = tokenize(code, "test").unwrap();
+        assert_eq!(tokens.len(), 1, "expected exactly one token from {code:?}");
+        tokens.remove(0)
+    }
+
+    #[test]
+    fn decodes_single_character_escapes() {
+        let token = lex_one(r#""a\nb\tc\rd\\e\0f""#);
+        assert_eq!(
+            token.kind,
+            TokenKind::StringLiteral(Cow::Owned("a\nb\tc\rd\\e\0f".into()))
+        );
+    }
+
+    #[test]
+    fn decodes_escaped_double_quote() {
+        // The jasm source is the 4 bytes: " \ " " — an escaped quote inside
+        // a double-quoted string literal.
+        let code = format!("{}{}{}{}", '"', '\\', '"', '"');
+        let token = lex_one(&code);
+        assert_eq!(token.kind, TokenKind::StringLiteral(Cow::Owned("\"".into())));
+    }
+
+    #[test]
+    fn decodes_escaped_single_quote() {
+        // The jasm source is the 4 bytes: ' \ ' ' — an escaped quote inside
+        // a single-quoted string literal.
+        let code = format!("{}{}{}{}", '\'', '\\', '\'', '\'');
+        let token = lex_one(&code);
+        assert_eq!(token.kind, TokenKind::StringLiteral(Cow::Owned("'".into())));
+    }
+
+    #[test]
+    fn unknown_escape_is_invalid_escape() {
+        let err = tokenize(r#""\q""#, "test").unwrap_err();
+        assert!(matches!(err.error_kind, LexerErrorKind::InvalidEscape('q')));
+    }
+
+    #[test]
+    fn decodes_unicode_escape() {
+        let token = lex_one(r#""\u{1F600}""#);
+        assert_eq!(
+            token.kind,
+            TokenKind::StringLiteral(Cow::Owned("\u{1F600}".into()))
+        );
+    }
+
+    #[test]
+    fn string_with_no_escapes_stays_borrowed() {
+        let token = lex_one(r#""plain""#);
+        match token.kind {
+            TokenKind::StringLiteral(Cow::Borrowed("plain")) => {}
+            other => panic!("expected a borrowed lexeme, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unicode_escape_without_opening_brace_reports_the_consumed_byte() {
+        let err = tokenize(r#""\u9""#, "test").unwrap_err();
+        assert!(matches!(err.error_kind, LexerErrorKind::InvalidEscape('u')));
+        assert_eq!(err.snippet, "\"\\u9");
+    }
+
+    #[test]
+    fn unterminated_unicode_escape_is_unexpected_eof() {
+        let err = tokenize(r#""\u{41""#, "test").unwrap_err();
+        assert!(matches!(err.error_kind, LexerErrorKind::UnexpectedEof(_)));
+        assert_eq!(err.snippet, "\"\\u{41\"");
+    }
+
+    #[test]
+    fn hash_line_comment_is_skipped() {
+        let tokens = tokenize("1 # a comment\n2 ", "test").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::IntegerLiteral(1));
+        assert_eq!(tokens[1].kind, TokenKind::IntegerLiteral(2));
+    }
+
+    #[test]
+    fn double_slash_line_comment_is_skipped() {
+        let tokens = tokenize("1 // a comment\n2 ", "test").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::IntegerLiteral(1));
+        assert_eq!(tokens[1].kind, TokenKind::IntegerLiteral(2));
+    }
+
+    #[test]
+    fn block_comment_is_skipped() {
+        let tokens = tokenize("1 /* skip\nme */ 2 ", "test").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::IntegerLiteral(1));
+        assert_eq!(tokens[1].kind, TokenKind::IntegerLiteral(2));
+    }
+
+    #[test]
+    fn nested_block_comments_are_balanced() {
+        let tokens = tokenize("1 /* outer /* inner */ still outer */ 2 ", "test").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::IntegerLiteral(1));
+        assert_eq!(tokens[1].kind, TokenKind::IntegerLiteral(2));
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_unexpected_eof() {
+        let err = tokenize("1 /* never closed", "test").unwrap_err();
+        assert!(matches!(err.error_kind, LexerErrorKind::UnexpectedEof(_)));
+    }
+
+    #[test]
+    fn two_char_operators_win_over_their_single_char_prefix() {
+        let cases = [
+            ("== ", TokenKind::EqualsEquals),
+            ("!= ", TokenKind::NotEquals),
+            ("<= ", TokenKind::LessEquals),
+            (">= ", TokenKind::GreaterEquals),
+            ("&& ", TokenKind::LogicalAnd),
+            ("|| ", TokenKind::LogicalOr),
+            ("-> ", TokenKind::Arrow),
+        ];
+        for (code, expected) in cases {
+            let tokens = tokenize(code, "test").unwrap();
+            assert_eq!(tokens[0].kind, expected, "lexing {code:?}");
         }
-        Err(err) => {
-            println!("{}", err);
+    }
+
+    #[test]
+    fn single_char_operators_stand_alone_without_their_partner() {
+        let cases = [
+            ("= ", TokenKind::Equals),
+            ("< ", TokenKind::Less),
+            ("> ", TokenKind::Greater),
+            ("& ", TokenKind::And),
+            ("| ", TokenKind::Or),
+            ("- ", TokenKind::Minus),
+        ];
+        for (code, expected) in cases {
+            let tokens = tokenize(code, "test").unwrap();
+            assert_eq!(tokens[0].kind, expected, "lexing {code:?}");
         }
     }
-    Ok(())
+
+    #[test]
+    fn two_char_operator_span_covers_both_bytes() {
+        let tokens = tokenize("== ", "test").unwrap();
+        assert_eq!(tokens[0].span, 0..2);
+    }
+
+    #[test]
+    fn lone_bang_is_an_unexpected_character() {
+        let err = tokenize("! ", "test").unwrap_err();
+        assert!(matches!(err.error_kind, LexerErrorKind::UnexpectedCharacter('!')));
+    }
+
+    #[test]
+    fn keyword_at_true_eof_is_not_truncated() {
+        let tokens = tokenize("end", "test").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::End);
+        assert_eq!(tokens[0].span, 0..3);
+    }
+
+    #[test]
+    fn identifier_at_true_eof_is_not_truncated() {
+        let tokens = tokenize("foo", "test").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::Identifier("foo"));
+        assert_eq!(tokens[0].span, 0..3);
+    }
+
+    #[test]
+    fn number_at_true_eof_is_not_truncated() {
+        let tokens = tokenize("123", "test").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::IntegerLiteral(123));
+        assert_eq!(tokens[0].span, 0..3);
+    }
+
+    #[test]
+    fn lexer_yields_tokens_one_at_a_time() {
+        let mut lexer = Lexer::new("1 + 2 ", "test");
+        assert_eq!(lexer.next().unwrap().unwrap().kind, TokenKind::IntegerLiteral(1));
+        assert_eq!(lexer.next().unwrap().unwrap().kind, TokenKind::Plus);
+        assert_eq!(lexer.next().unwrap().unwrap().kind, TokenKind::IntegerLiteral(2));
+        assert!(lexer.next().is_none());
+    }
+
+    #[test]
+    fn lexer_fuses_after_an_error() {
+        let mut lexer = Lexer::new(r#""\u9""#, "test");
+        assert!(lexer.next().unwrap().is_err());
+        assert!(
+            lexer.next().is_none(),
+            "Iterator::next must not resume scanning after an error"
+        );
+    }
+
+    #[test]
+    fn scan_one_does_not_fuse_so_tokenize_recovering_can_keep_going() {
+        let outcome = tokenize_recovering("let $ a = 1;", "test");
+        assert_eq!(outcome.errors.len(), 1);
+        assert_eq!(
+            outcome.tokens.iter().map(|t| &t.kind).collect::<Vec<_>>(),
+            vec![
+                &TokenKind::Let,
+                &TokenKind::Identifier("a"),
+                &TokenKind::Equals,
+                &TokenKind::IntegerLiteral(1),
+                &TokenKind::SemiColon,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_recovering_accumulates_every_error_in_one_pass() {
+        let outcome = tokenize_recovering("let $ a = @ 1;", "test");
+        assert_eq!(outcome.errors.len(), 2);
+        assert_eq!(
+            outcome.tokens.iter().map(|t| &t.kind).collect::<Vec<_>>(),
+            vec![
+                &TokenKind::Let,
+                &TokenKind::Identifier("a"),
+                &TokenKind::Equals,
+                &TokenKind::IntegerLiteral(1),
+                &TokenKind::SemiColon,
+            ]
+        );
+    }
 }
@@ -0,0 +1,438 @@
+#![allow(dead_code)]
+
+use std::borrow::Cow;
+
+use crate::{Token, TokenKind};
+
+/// An expression, as produced by [`Parser::parse_expr`].
+#[derive(Debug)]
+pub(crate) enum Expr<'a> {
+    IntegerLiteral(i64),
+    FloatLiteral(f64),
+    StringLiteral(Cow<'a, str>),
+    Identifier(&'a str),
+    Binary {
+        op: TokenKind<'a>,
+        left: Box<Expr<'a>>,
+        right: Box<Expr<'a>>,
+    },
+}
+
+/// A statement, as produced by [`Parser::parse_stmt`].
+#[derive(Debug)]
+pub(crate) enum Stmt<'a> {
+    Let {
+        name: &'a str,
+        value: Expr<'a>,
+    },
+    Var {
+        name: &'a str,
+        value: Expr<'a>,
+    },
+    Def {
+        name: &'a str,
+        params: Vec<&'a str>,
+        body: Vec<Stmt<'a>>,
+    },
+    Func {
+        name: &'a str,
+        params: Vec<&'a str>,
+        body: Vec<Stmt<'a>>,
+    },
+    Struct {
+        name: &'a str,
+        fields: Vec<&'a str>,
+    },
+    Repeat {
+        body: Vec<Stmt<'a>>,
+        condition: Expr<'a>,
+    },
+    Expr(Expr<'a>),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{line}:{col}\nParse error:\n\t{message}")]
+pub(crate) struct ParseError {
+    message: String,
+    line: usize,
+    col: usize,
+}
+
+/// Returns the binding power of a binary operator, or `None` if `kind` is not one.
+///
+/// Higher numbers bind tighter, lowest to highest: `LogicalOr`, `LogicalAnd`,
+/// `Or`/`Xor`, `And`, `EqualsEquals`/`NotEquals`, the relational operators
+/// (`Less`/`LessEquals`/`Greater`/`GreaterEquals`), `Plus`/`Minus`, then
+/// `Multiply`/`Divide`/`Modulo`. `Arrow` is a function-signature separator,
+/// not a binary expression operator, so it has no binding power.
+fn binding_power(kind: &TokenKind) -> Option<u8> {
+    match kind {
+        TokenKind::LogicalOr => Some(1),
+        TokenKind::LogicalAnd => Some(2),
+        TokenKind::Or | TokenKind::Xor => Some(3),
+        TokenKind::And => Some(4),
+        TokenKind::EqualsEquals | TokenKind::NotEquals => Some(5),
+        TokenKind::Less | TokenKind::LessEquals | TokenKind::Greater | TokenKind::GreaterEquals => {
+            Some(6)
+        }
+        TokenKind::Plus | TokenKind::Minus => Some(7),
+        TokenKind::Multiply | TokenKind::Divide | TokenKind::Modulo => Some(8),
+        _ => None,
+    }
+}
+
+/// Consumes a token slice and produces an AST, one statement at a time.
+pub(crate) struct Parser<'a> {
+    tokens: &'a [Token<'a>],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(tokens: &'a [Token<'a>]) -> Self {
+        Parser { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<&Token<'a>> {
+        let token = self.tokens.get(self.pos);
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        let (line, col) = self
+            .peek()
+            .or_else(|| self.tokens.last())
+            .map(|token| (token.line, token.col))
+            .unwrap_or((0, 0));
+        ParseError {
+            message: message.into(),
+            line,
+            col,
+        }
+    }
+
+    fn expect(&mut self, kind: &TokenKind) -> Result<&Token<'a>, ParseError> {
+        match self.peek() {
+            Some(token) if &token.kind == kind => Ok(self.advance().unwrap()),
+            Some(token) => Err(ParseError {
+                message: format!("expected {:?}, found {:?}", kind, token.kind),
+                line: token.line,
+                col: token.col,
+            }),
+            None => Err(self.error(format!("expected {:?}, found end of input", kind))),
+        }
+    }
+
+    fn expect_identifier(&mut self) -> Result<&'a str, ParseError> {
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::Identifier(name),
+                ..
+            }) => {
+                let name = *name;
+                self.advance();
+                Ok(name)
+            }
+            Some(token) => Err(ParseError {
+                message: format!("expected an identifier, found {:?}", token.kind),
+                line: token.line,
+                col: token.col,
+            }),
+            None => Err(self.error("expected an identifier, found end of input")),
+        }
+    }
+
+    /// Parses every statement in the token slice.
+    pub(crate) fn parse(&mut self) -> Result<Vec<Stmt<'a>>, ParseError> {
+        let mut stmts = vec![];
+        while self.peek().is_some() {
+            stmts.push(self.parse_stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn parse_stmt(&mut self) -> Result<Stmt<'a>, ParseError> {
+        match self.peek().map(|token| &token.kind) {
+            Some(TokenKind::Let) => self.parse_binding(true),
+            Some(TokenKind::Var) => self.parse_binding(false),
+            Some(TokenKind::Def) => self.parse_function(true),
+            Some(TokenKind::Func) => self.parse_function(false),
+            Some(TokenKind::Struct) => self.parse_struct(),
+            Some(TokenKind::Repeat) => self.parse_repeat(),
+            _ => {
+                let expr = self.parse_expr(0)?;
+                self.expect(&TokenKind::SemiColon)?;
+                Ok(Stmt::Expr(expr))
+            }
+        }
+    }
+
+    fn parse_binding(&mut self, is_let: bool) -> Result<Stmt<'a>, ParseError> {
+        self.advance();
+        let name = self.expect_identifier()?;
+        self.expect(&TokenKind::Equals)?;
+        let value = self.parse_expr(0)?;
+        self.expect(&TokenKind::SemiColon)?;
+        Ok(if is_let {
+            Stmt::Let { name, value }
+        } else {
+            Stmt::Var { name, value }
+        })
+    }
+
+    fn parse_params(&mut self) -> Result<Vec<&'a str>, ParseError> {
+        let mut params = vec![];
+        if matches!(self.peek().map(|token| &token.kind), Some(TokenKind::Identifier(_))) {
+            params.push(self.expect_identifier()?);
+            while matches!(self.peek().map(|token| &token.kind), Some(TokenKind::Comma)) {
+                self.advance();
+                params.push(self.expect_identifier()?);
+            }
+        }
+        Ok(params)
+    }
+
+    fn parse_block(&mut self) -> Result<Vec<Stmt<'a>>, ParseError> {
+        let mut body = vec![];
+        while !matches!(self.peek().map(|token| &token.kind), Some(TokenKind::End) | None) {
+            body.push(self.parse_stmt()?);
+        }
+        self.expect(&TokenKind::End)?;
+        Ok(body)
+    }
+
+    fn parse_function(&mut self, is_def: bool) -> Result<Stmt<'a>, ParseError> {
+        self.advance();
+        let name = self.expect_identifier()?;
+        self.expect(&TokenKind::Colon)?;
+        let params = self.parse_params()?;
+        self.expect(&TokenKind::SemiColon)?;
+        let body = self.parse_block()?;
+        Ok(if is_def {
+            Stmt::Def { name, params, body }
+        } else {
+            Stmt::Func { name, params, body }
+        })
+    }
+
+    fn parse_struct(&mut self) -> Result<Stmt<'a>, ParseError> {
+        self.advance();
+        let name = self.expect_identifier()?;
+        self.expect(&TokenKind::Colon)?;
+        let fields = self.parse_params()?;
+        self.expect(&TokenKind::End)?;
+        Ok(Stmt::Struct { name, fields })
+    }
+
+    fn parse_repeat(&mut self) -> Result<Stmt<'a>, ParseError> {
+        self.advance();
+        let mut body = vec![];
+        while !matches!(self.peek().map(|token| &token.kind), Some(TokenKind::Until) | None) {
+            body.push(self.parse_stmt()?);
+        }
+        self.expect(&TokenKind::Until)?;
+        let condition = self.parse_expr(0)?;
+        self.expect(&TokenKind::SemiColon)?;
+        Ok(Stmt::Repeat { body, condition })
+    }
+
+    /// Precedence-climbing expression parser: parses a primary expression, then
+    /// keeps folding in binary operators whose precedence is at least `min_bp`,
+    /// recursing with `min_bp = prec + 1` so operators of equal precedence are
+    /// left-associative.
+    fn parse_expr(&mut self, min_bp: u8) -> Result<Expr<'a>, ParseError> {
+        let mut left = self.parse_primary()?;
+        while let Some(prec) = self.peek().and_then(|token| binding_power(&token.kind)) {
+            if prec < min_bp {
+                break;
+            }
+            let op = self.advance().unwrap().kind.clone();
+            let right = self.parse_expr(prec + 1)?;
+            left = Expr::Binary {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr<'a>, ParseError> {
+        match self
+            .advance()
+            .map(|token| (token.kind.clone(), token.line, token.col))
+        {
+            Some((TokenKind::IntegerLiteral(value), ..)) => Ok(Expr::IntegerLiteral(value)),
+            Some((TokenKind::FloatLiteral(value), ..)) => Ok(Expr::FloatLiteral(value)),
+            Some((TokenKind::StringLiteral(value), ..)) => Ok(Expr::StringLiteral(value)),
+            Some((TokenKind::Identifier(name), ..)) => Ok(Expr::Identifier(name)),
+            Some((kind, line, col)) => Err(ParseError {
+                message: format!("expected an expression, found {:?}", kind),
+                line,
+                col,
+            }),
+            None => Err(self.error("expected an expression, found end of input")),
+        }
+    }
+}
+
+/// Parses a full token slice into a sequence of statements.
+pub(crate) fn parse<'a>(tokens: &'a [Token<'a>]) -> Result<Vec<Stmt<'a>>, ParseError> {
+    Parser::new(tokens).parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenize;
+
+    fn parse_str<'a>(code: &'a str, tokens: &'a mut Vec<Token<'a>>) -> Result<Vec<Stmt<'a>>, ParseError> {
+        *tokens = tokenize(code, "test").unwrap();
+        parse(tokens)
+    }
+
+    #[test]
+    fn precedence_climbing_respects_binding_power() {
+        let mut tokens = vec![];
+        let stmts = parse_str("1 + 2 * 3;", &mut tokens).unwrap();
+        match &stmts[..] {
+            [Stmt::Expr(Expr::Binary { op, left, right })] => {
+                assert_eq!(*op, TokenKind::Plus);
+                assert!(matches!(**left, Expr::IntegerLiteral(1)));
+                match &**right {
+                    Expr::Binary { op, left, right } => {
+                        assert_eq!(*op, TokenKind::Multiply);
+                        assert!(matches!(**left, Expr::IntegerLiteral(2)));
+                        assert!(matches!(**right, Expr::IntegerLiteral(3)));
+                    }
+                    other => panic!("expected a binary expr, got {other:?}"),
+                }
+            }
+            other => panic!("expected a single binary expr statement, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn equal_precedence_is_left_associative() {
+        let mut tokens = vec![];
+        let stmts = parse_str("1 - 2 - 3;", &mut tokens).unwrap();
+        match &stmts[..] {
+            [Stmt::Expr(Expr::Binary { op, left, right })] => {
+                assert_eq!(*op, TokenKind::Minus);
+                assert!(matches!(**right, Expr::IntegerLiteral(3)));
+                match &**left {
+                    Expr::Binary { op, left, right } => {
+                        assert_eq!(*op, TokenKind::Minus);
+                        assert!(matches!(**left, Expr::IntegerLiteral(1)));
+                        assert!(matches!(**right, Expr::IntegerLiteral(2)));
+                    }
+                    other => panic!("expected a binary expr, got {other:?}"),
+                }
+            }
+            other => panic!("expected a single binary expr statement, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_let_binding() {
+        let mut tokens = vec![];
+        let stmts = parse_str("let x = 1;", &mut tokens).unwrap();
+        match &stmts[..] {
+            [Stmt::Let { name, value }] => {
+                assert_eq!(*name, "x");
+                assert!(matches!(value, Expr::IntegerLiteral(1)));
+            }
+            other => panic!("expected a single let statement, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_semicolon_is_a_parse_error() {
+        let mut tokens = vec![];
+        let err = parse_str("let x = 1 y;", &mut tokens).unwrap_err();
+        assert!(err.to_string().contains("expected SemiColon"));
+    }
+
+    #[test]
+    fn parses_var_binding() {
+        let mut tokens = vec![];
+        let stmts = parse_str("var x = 1;", &mut tokens).unwrap();
+        match &stmts[..] {
+            [Stmt::Var { name, value }] => {
+                assert_eq!(*name, "x");
+                assert!(matches!(value, Expr::IntegerLiteral(1)));
+            }
+            other => panic!("expected a single var statement, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_def_with_params_and_body() {
+        // `end` is the very last token in the input, with no trailing
+        // whitespace, to exercise the EOF path as well as Def parsing.
+        let mut tokens = vec![];
+        let stmts = parse_str("def f: a, b;\n1;\nend", &mut tokens).unwrap();
+        match &stmts[..] {
+            [Stmt::Def { name, params, body }] => {
+                assert_eq!(*name, "f");
+                assert_eq!(params, &["a", "b"]);
+                match &body[..] {
+                    [Stmt::Expr(Expr::IntegerLiteral(1))] => {}
+                    other => panic!("expected a single expr statement body, got {other:?}"),
+                }
+            }
+            other => panic!("expected a single def statement, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_func_with_no_params() {
+        let mut tokens = vec![];
+        let stmts = parse_str("func g:;\nend", &mut tokens).unwrap();
+        match &stmts[..] {
+            [Stmt::Func { name, params, body }] => {
+                assert_eq!(*name, "g");
+                assert!(params.is_empty());
+                assert!(body.is_empty());
+            }
+            other => panic!("expected a single func statement, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_struct_with_fields() {
+        // `end` is the very last token in the input, with no trailing
+        // whitespace.
+        let mut tokens = vec![];
+        let stmts = parse_str("struct Point: x, y end", &mut tokens).unwrap();
+        match &stmts[..] {
+            [Stmt::Struct { name, fields }] => {
+                assert_eq!(*name, "Point");
+                assert_eq!(fields, &["x", "y"]);
+            }
+            other => panic!("expected a single struct statement, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_repeat_until() {
+        let mut tokens = vec![];
+        let stmts = parse_str("repeat\n1;\nuntil 0;", &mut tokens).unwrap();
+        match &stmts[..] {
+            [Stmt::Repeat { body, condition }] => {
+                match &body[..] {
+                    [Stmt::Expr(Expr::IntegerLiteral(1))] => {}
+                    other => panic!("expected a single expr statement body, got {other:?}"),
+                }
+                assert!(matches!(condition, Expr::IntegerLiteral(0)));
+            }
+            other => panic!("expected a single repeat statement, got {other:?}"),
+        }
+    }
+}